@@ -1,13 +1,14 @@
 use libafl::corpus::{Corpus, HasCurrentCorpusId, InMemoryOnDiskCorpus};
 use libafl::executors::InProcessExecutor;
 use libafl::inputs::BytesInput;
+use libafl::monitors::tui::TuiMonitor;
 use libafl::monitors::SimpleMonitor;
 use libafl::stages::{ObserverEqualityFactory, StagesTuple, StdTMinMutationalStage};
 use libafl::state::HasSolutions;
 use libafl::Error;
 use libafl::{
     corpus::{InMemoryCorpus, OnDiskCorpus},
-    events::SimpleEventManager,
+    events::{EventConfig, Launcher, SimpleEventManager},
     executors::ExitKind,
     feedbacks::{CrashFeedback, MaxMapFeedback},
     fuzzer::{Fuzzer, StdFuzzer},
@@ -19,7 +20,15 @@ use libafl::{
     stages::mutational::StdMutationalStage,
     state::{HasCorpus, StdState},
 };
-use libafl_bolts::{nonnull_raw_mut, nonzero, rands::StdRand, tuples::tuple_list, AsSlice};
+use libafl_bolts::{
+    core_affinity::{CoreId, Cores},
+    nonnull_raw_mut,
+    nonzero,
+    rands::StdRand,
+    shmem::StdShMemProvider,
+    tuples::tuple_list,
+    AsSlice,
+};
 use std::fs;
 use std::path::Path;
 use std::{path::PathBuf, ptr::write};
@@ -34,6 +43,14 @@ fn signals_set(idx: usize) {
     unsafe { write(SIGNALS_PTR.add(idx), 1) };
 }
 
+/// Whether to use the `TuiMonitor` (the default) instead of a plain `SimpleMonitor` for the
+/// multi-core fuzzing phase. Set `HELLO_LIBAFL_TUI=0` to fall back to `SimpleMonitor`.
+fn tui_enabled() -> bool {
+    std::env::var("HELLO_LIBAFL_TUI")
+        .map(|v| v != "0")
+        .unwrap_or(true)
+}
+
 /// Print inputs from a directory
 ///
 /// Reads all files in the given directory and displays them sorted by size.
@@ -99,9 +116,21 @@ fn harness(input: &BytesInput) -> ExitKind {
     ExitKind::Ok
 }
 
-pub fn main() -> Result<(), Error> {
-    delete_cache_files().expect("Failed to delete cache files");
-
+/// Runs one fuzzer worker until it finds a solution, then returns so the `Launcher` can
+/// bring the process down cleanly; `state` is `Some` on a restart, carrying over the
+/// corpus/solutions shared with the other workers over LLMP.
+#[allow(clippy::type_complexity)]
+fn run_client<EM>(
+    state: Option<StdState<InMemoryOnDiskCorpus<BytesInput>, BytesInput, StdRand, OnDiskCorpus<BytesInput>>>,
+    mut mgr: EM,
+    _core_id: CoreId,
+) -> Result<(), Error>
+where
+    EM: libafl::events::EventFirer<State = StdState<InMemoryOnDiskCorpus<BytesInput>, BytesInput, StdRand, OnDiskCorpus<BytesInput>>>
+        + libafl::events::EventRestarter<State = StdState<InMemoryOnDiskCorpus<BytesInput>, BytesInput, StdRand, OnDiskCorpus<BytesInput>>>
+        + libafl::events::ProgressReporter<State = StdState<InMemoryOnDiskCorpus<BytesInput>, BytesInput, StdRand, OnDiskCorpus<BytesInput>>>
+        + libafl::events::EventProcessor<State = StdState<InMemoryOnDiskCorpus<BytesInput>, BytesInput, StdRand, OnDiskCorpus<BytesInput>>>,
+{
     // The closure that we want to fuzz
     let mut to_fuzz = harness;
 
@@ -116,30 +145,28 @@ pub fn main() -> Result<(), Error> {
     // A feedback to choose if an input is a solution or not
     let mut objective = CrashFeedback::new();
 
-    // The Monitor trait define how the fuzzer stats are displayed to the user
-    let mon = SimpleMonitor::new(|s| println!("{s}"));
-
-    let mut mgr = SimpleEventManager::new(mon);
-
     let corpus_dir = PathBuf::from("./corpus");
     let solution_dir = PathBuf::from("./solutions");
 
-    // create a State from scratch
-    let mut state = StdState::new(
-        // RNG
-        StdRand::new(),
-        // Corpus that will be evolved, we keep it in memory for performance
-        InMemoryOnDiskCorpus::new(corpus_dir).unwrap(),
-        // Corpus in which we store solutions (crashes in this example),
-        // on disk so the user can get them after stopping the fuzzer
-        OnDiskCorpus::new(&solution_dir).unwrap(),
-        // States of the feedbacks.
-        // The feedbacks can report the data that should persist in the State.
-        &mut feedback,
-        // Same for objective feedbacks
-        &mut objective,
-    )
-    .unwrap();
+    // create a State from scratch, or reuse the one handed back to us across a restart
+    let mut state = match state {
+        Some(state) => state,
+        None => StdState::new(
+            // RNG
+            StdRand::new(),
+            // Corpus that will be evolved, we keep it in memory for performance
+            InMemoryOnDiskCorpus::new(corpus_dir).unwrap(),
+            // Corpus in which we store solutions (crashes in this example),
+            // on disk so the user can get them after stopping the fuzzer
+            OnDiskCorpus::new(&solution_dir).unwrap(),
+            // States of the feedbacks.
+            // The feedbacks can report the data that should persist in the State.
+            &mut feedback,
+            // Same for objective feedbacks
+            &mut objective,
+        )
+        .unwrap(),
+    };
 
     // A queue policy to get testcasess from the corpus
     let scheduler = QueueScheduler::new();
@@ -160,10 +187,13 @@ pub fn main() -> Result<(), Error> {
     // Generator of printable bytearrays of max size 32
     let mut generator = RandPrintablesGenerator::new(nonzero!(32));
 
-    // Generate 8 initial inputs
-    state
-        .generate_initial_inputs(&mut fuzzer, &mut executor, &mut generator, &mut mgr, 8)
-        .expect("Failed to generate the initial corpus");
+    // Generate the initial corpus, only the first time this worker starts (a restart
+    // already carries the evolved corpus over in `state`)
+    if state.must_load_initial_inputs() {
+        state
+            .generate_initial_inputs(&mut fuzzer, &mut executor, &mut generator, &mut mgr, 8)
+            .expect("Failed to generate the initial corpus");
+    }
 
     // Setup a mutational stage with a basic bytes mutator
     let mutator = HavocScheduledMutator::new(havoc_mutations());
@@ -177,9 +207,57 @@ pub fn main() -> Result<(), Error> {
         fuzzer.fuzz_one(&mut stages, &mut executor, &mut state, &mut mgr)?;
     }
 
+    Ok(())
+}
+
+pub fn main() -> Result<(), Error> {
+    delete_cache_files().expect("Failed to delete cache files");
+
+    // One worker per selected core, corpus/solutions shared over LLMP, and auto-restart
+    // after a crash; each worker stops itself once it has found a solution.
+    let cores = Cores::from_cmdline("all").expect("Failed to parse cores");
+
+    // `TuiMonitor` by default; set `HELLO_LIBAFL_TUI=0` to fall back to `SimpleMonitor`
+    // instead (its own concrete type, hence the duplicated `Launcher::builder()` chain).
+    let launch_result = if tui_enabled() {
+        let shmem_provider = StdShMemProvider::new().expect("Failed to init shared memory");
+        let monitor = TuiMonitor::builder().title("baby_fuzzer_minimizing").build();
+        Launcher::builder()
+            .shmem_provider(shmem_provider)
+            .configuration(EventConfig::from_name("baby-fuzzer-minimizing"))
+            .monitor(monitor)
+            .run_client(&mut run_client)
+            .cores(&cores)
+            .broker_port(1339)
+            .build()
+            .launch()
+    } else {
+        let shmem_provider = StdShMemProvider::new().expect("Failed to init shared memory");
+        let monitor = SimpleMonitor::new(|s| println!("{s}"));
+        Launcher::builder()
+            .shmem_provider(shmem_provider)
+            .configuration(EventConfig::from_name("baby-fuzzer-minimizing"))
+            .monitor(monitor)
+            .run_client(&mut run_client)
+            .cores(&cores)
+            .broker_port(1339)
+            .build()
+            .launch()
+    };
+
+    match launch_result {
+        Ok(()) => (),
+        Err(Error::ShuttingDown) => println!("Fuzzing stopped by user. Good bye."),
+        Err(err) => panic!("Failed to run launcher: {err:?}"),
+    }
+
     // ============================== Start minimization ==============================
+    // A single-process pass over the solutions the workers wrote to disk, so it stays a
+    // plain `SimpleEventManager` run rather than another multi-core campaign.
 
+    let solution_dir = PathBuf::from("./solutions");
     let minimized_dir = PathBuf::from("./minimized");
+    let mut to_fuzz = harness;
 
     let mut state = StdState::new(
         StdRand::new(),
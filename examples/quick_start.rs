@@ -2,10 +2,10 @@ use std::fs;
 use std::path::Path;
 use std::{path::PathBuf, ptr::write};
 
-use libafl::monitors::SimpleMonitor;
+use libafl::monitors::{tui::TuiMonitor, SimpleMonitor};
 use libafl::{
     corpus::{InMemoryCorpus, OnDiskCorpus},
-    events::SimpleEventManager,
+    events::{EventConfig, Launcher},
     executors::{ExitKind, InProcessExecutor},
     feedbacks::{CrashFeedback, MaxMapFeedback},
     fuzzer::{Fuzzer, StdFuzzer},
@@ -16,9 +16,17 @@ use libafl::{
     schedulers::QueueScheduler,
     stages::mutational::StdMutationalStage,
     state::StdState,
+    Error,
 };
 use libafl_bolts::{
-    current_nanos, nonnull_raw_mut, nonzero, rands::StdRand, tuples::tuple_list, AsSlice,
+    core_affinity::{CoreId, Cores},
+    current_nanos,
+    nonnull_raw_mut,
+    nonzero,
+    rands::StdRand,
+    shmem::StdShMemProvider,
+    tuples::tuple_list,
+    AsSlice,
 };
 
 /// Coverage map with explicit assignments due to the lack of instrumentation
@@ -31,6 +39,14 @@ fn signals_set(idx: usize) {
     unsafe { write(SIGNALS_PTR.add(idx), 1) };
 }
 
+/// Whether to use the `TuiMonitor` (the default) instead of a plain `SimpleMonitor`. Set
+/// `HELLO_LIBAFL_TUI=0` to fall back to `SimpleMonitor`.
+fn tui_enabled() -> bool {
+    std::env::var("HELLO_LIBAFL_TUI")
+        .map(|v| v != "0")
+        .unwrap_or(true)
+}
+
 /// The closure that we want to fuzz
 fn harness(input: &BytesInput) -> ExitKind {
     let target = input.target_bytes();
@@ -48,9 +64,22 @@ fn harness(input: &BytesInput) -> ExitKind {
     ExitKind::Ok
 }
 
-pub fn main() {
-    delete_cache_files().expect("Failed to delete cache files");
-
+/// Runs one fuzzer worker. Called once per core by the `Launcher`, and again on every
+/// restart after the in-process panic fires; `state` is `Some` on a restart, carrying over
+/// the corpus/solutions shared with the other workers over LLMP. `SIGNALS` is a plain
+/// process-local static, so each worker naturally gets its own copy.
+#[allow(clippy::type_complexity)]
+fn run_client<EM>(
+    state: Option<StdState<InMemoryCorpus<BytesInput>, BytesInput, StdRand, OnDiskCorpus<BytesInput>>>,
+    mut mgr: EM,
+    _core_id: CoreId,
+) -> Result<(), Error>
+where
+    EM: libafl::events::EventFirer<State = StdState<InMemoryCorpus<BytesInput>, BytesInput, StdRand, OnDiskCorpus<BytesInput>>>
+        + libafl::events::EventRestarter<State = StdState<InMemoryCorpus<BytesInput>, BytesInput, StdRand, OnDiskCorpus<BytesInput>>>
+        + libafl::events::ProgressReporter<State = StdState<InMemoryCorpus<BytesInput>, BytesInput, StdRand, OnDiskCorpus<BytesInput>>>
+        + libafl::events::EventProcessor<State = StdState<InMemoryCorpus<BytesInput>, BytesInput, StdRand, OnDiskCorpus<BytesInput>>>,
+{
     // Bind harness function to extend its lifetime
     let mut to_fuzz = harness;
 
@@ -63,29 +92,25 @@ pub fn main() {
     // A feedback to choose if an input is a solution or not
     let mut objective = CrashFeedback::new();
 
-    // create a State from scratch
-    let mut state = StdState::new(
-        // RNG
-        StdRand::with_seed(current_nanos()),
-        // Corpus that will be evolved, we keep it in memory for performance
-        InMemoryCorpus::new(),
-        // Corpus in which we store solutions (crashes in this example),
-        // on disk so the user can get them after stopping the fuzzer
-        OnDiskCorpus::new(PathBuf::from("./crashes")).unwrap(),
-        // States of the feedbacks.
-        // The feedbacks can report the data that should persist in the State.
-        &mut feedback,
-        // Same for objective feedbacks
-        &mut objective,
-    )
-    .unwrap();
-
-    // The Monitor trait define how the fuzzer stats are displayed to the user
-    let mon = SimpleMonitor::new(|s| println!("{s}"));
-
-    // The event manager handle the various events generated during the fuzzing loop
-    // such as the notification of the addition of a new item to the corpus
-    let mut mgr = SimpleEventManager::new(mon);
+    // create a State from scratch, or reuse the one handed back to us across a restart
+    let mut state = match state {
+        Some(state) => state,
+        None => StdState::new(
+            // RNG
+            StdRand::with_seed(current_nanos()),
+            // Corpus that will be evolved, we keep it in memory for performance
+            InMemoryCorpus::new(),
+            // Corpus in which we store solutions (crashes in this example),
+            // on disk so the user can get them after stopping the fuzzer
+            OnDiskCorpus::new(PathBuf::from("./crashes")).unwrap(),
+            // States of the feedbacks.
+            // The feedbacks can report the data that should persist in the State.
+            &mut feedback,
+            // Same for objective feedbacks
+            &mut objective,
+        )
+        .unwrap(),
+    };
 
     // A queue policy to get testcasess from the corpus
     let scheduler = QueueScheduler::new();
@@ -106,18 +131,62 @@ pub fn main() {
     // Generator of printable bytearrays of max size 32
     let mut generator = RandPrintablesGenerator::new(nonzero!(32));
 
-    // Generate 8 initial inputs
-    state
-        .generate_initial_inputs(&mut fuzzer, &mut executor, &mut generator, &mut mgr, 8)
-        .expect("Failed to generate the initial corpus");
+    // Generate the initial corpus, only the first time this worker starts (a restart
+    // already carries the evolved corpus over in `state`)
+    if state.must_load_initial_inputs() {
+        state
+            .generate_initial_inputs(&mut fuzzer, &mut executor, &mut generator, &mut mgr, 8)
+            .expect("Failed to generate the initial corpus");
+    }
 
     // Setup a mutational stage with a basic bytes mutator
     let mutator = HavocScheduledMutator::new(havoc_mutations());
     let mut stages = tuple_list!(StdMutationalStage::new(mutator));
 
-    fuzzer
-        .fuzz_loop(&mut stages, &mut executor, &mut state, &mut mgr)
-        .expect("Error in the fuzzing loop");
+    fuzzer.fuzz_loop(&mut stages, &mut executor, &mut state, &mut mgr)?;
+    Ok(())
+}
+
+pub fn main() {
+    delete_cache_files().expect("Failed to delete cache files");
+
+    // One worker per selected core, corpus/solutions shared over LLMP, and auto-restart
+    // after the in-process panic fires.
+    let cores = Cores::from_cmdline("all").expect("Failed to parse cores");
+
+    // `TuiMonitor` by default; set `HELLO_LIBAFL_TUI=0` to fall back to `SimpleMonitor`
+    // instead (its own concrete type, hence the duplicated `Launcher::builder()` chain).
+    let launch_result = if tui_enabled() {
+        let shmem_provider = StdShMemProvider::new().expect("Failed to init shared memory");
+        let monitor = TuiMonitor::builder().title("quick_start").build();
+        Launcher::builder()
+            .shmem_provider(shmem_provider)
+            .configuration(EventConfig::from_name("quick-start"))
+            .monitor(monitor)
+            .run_client(&mut run_client)
+            .cores(&cores)
+            .broker_port(1338)
+            .build()
+            .launch()
+    } else {
+        let shmem_provider = StdShMemProvider::new().expect("Failed to init shared memory");
+        let monitor = SimpleMonitor::new(|s| println!("{s}"));
+        Launcher::builder()
+            .shmem_provider(shmem_provider)
+            .configuration(EventConfig::from_name("quick-start"))
+            .monitor(monitor)
+            .run_client(&mut run_client)
+            .cores(&cores)
+            .broker_port(1338)
+            .build()
+            .launch()
+    };
+
+    match launch_result {
+        Ok(()) => (),
+        Err(Error::ShuttingDown) => println!("Fuzzing stopped by user. Good bye."),
+        Err(err) => panic!("Failed to run launcher: {err:?}"),
+    }
 }
 
 pub fn delete_cache_files() -> std::io::Result<()> {
@@ -0,0 +1,334 @@
+//! ABI-aware calldata generation and mutation: the seeds and `RandPrintablesGenerator` used
+//! to produce raw bytes with no notion of the 4-byte selector + 32-byte-word layout the EVM
+//! expects, so almost every mutated input was malformed calldata the contract rejected
+//! before reaching any interesting logic. This module generates and mutates calldata word
+//! by word instead, so the fuzzer spends its time on valid transactions.
+
+use std::borrow::Cow;
+
+use libafl::{
+    generators::Generator,
+    inputs::{BytesInput, HasMutatorBytes},
+    mutators::{MutationResult, Mutator},
+    state::HasRand,
+    Error,
+};
+use libafl_bolts::{rands::Rand, Named};
+
+/// The ABI types this module knows how to encode/mutate a word as.
+#[derive(Clone, Copy, Debug)]
+pub enum AbiType {
+    Uint256,
+    Bool,
+    Address,
+}
+
+/// A known function selector and the ABI types of its parameters, in order.
+#[derive(Clone, Copy, Debug)]
+pub struct FunctionAbi {
+    pub selector: [u8; 4],
+    pub params: &'static [AbiType],
+}
+
+/// `ToFuzz.sol`'s dispatch table: `Try(uint256)` and the parameterless flag getter.
+pub const KNOWN_FUNCTIONS: &[FunctionAbi] = &[
+    FunctionAbi {
+        selector: [0xeb, 0xd4, 0xb2, 0xf9],
+        params: &[AbiType::Uint256],
+    },
+    FunctionAbi {
+        selector: [0x89, 0x0e, 0xba, 0x68],
+        params: &[],
+    },
+];
+
+/// Boundary `uint256` values that tend to flip comparisons and off-by-one guards.
+const INTERESTING_UINT256_WORDS: &[[u8; 32]] = &[
+    [0u8; 32],
+    {
+        let mut w = [0u8; 32];
+        w[31] = 1;
+        w
+    },
+    [0xffu8; 32],
+    {
+        let mut w = [0u8; 32];
+        w[31] = 0x7f;
+        w
+    },
+    {
+        let mut w = [0u8; 32];
+        w[0] = 0x80;
+        w
+    },
+    {
+        let mut w = [0u8; 32];
+        w[30] = 1;
+        w
+    },
+];
+
+fn encode_word(rand: &mut impl Rand, ty: AbiType) -> [u8; 32] {
+    match ty {
+        AbiType::Uint256 => {
+            let idx = rand.below(INTERESTING_UINT256_WORDS.len().try_into().unwrap()) as usize;
+            INTERESTING_UINT256_WORDS[idx]
+        }
+        AbiType::Bool => {
+            let mut word = [0u8; 32];
+            word[31] = u8::from(rand.below(2.try_into().unwrap()) == 1);
+            word
+        }
+        AbiType::Address => {
+            let mut word = [0u8; 32];
+            rand.fill_bytes(&mut word[12..32]);
+            word
+        }
+    }
+}
+
+/// Generates calldata for a random known function: a valid selector followed by correctly
+/// sized, 32-byte-aligned encoded arguments, instead of raw printable bytes the EVM would
+/// reject before the ABI decoder even runs.
+#[derive(Debug, Default)]
+pub struct AbiCalldataGenerator;
+
+impl AbiCalldataGenerator {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl<S> Generator<BytesInput, S> for AbiCalldataGenerator
+where
+    S: HasRand,
+{
+    fn generate(&mut self, state: &mut S) -> Result<BytesInput, Error> {
+        let rand = state.rand_mut();
+        let function = KNOWN_FUNCTIONS[rand.below(KNOWN_FUNCTIONS.len().try_into().unwrap()) as usize];
+
+        let mut calldata = Vec::with_capacity(4 + function.params.len() * 32);
+        calldata.extend_from_slice(&function.selector);
+        for &param in function.params {
+            calldata.extend_from_slice(&encode_word(rand, param));
+        }
+
+        Ok(BytesInput::new(calldata))
+    }
+}
+
+/// Number of whole 32-byte words following the 4-byte selector.
+fn word_count(bytes: &[u8]) -> usize {
+    bytes.len().saturating_sub(4) / 32
+}
+
+fn word_range(word_idx: usize) -> std::ops::Range<usize> {
+    let start = 4 + word_idx * 32;
+    start..start + 32
+}
+
+/// Replaces a random argument word with an interesting `uint256` boundary value (0, 1, max,
+/// small ints, powers of two), preserving the 4-byte selector.
+#[derive(Debug, Default)]
+pub struct AbiUintWordMutator;
+
+impl AbiUintWordMutator {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Named for AbiUintWordMutator {
+    fn name(&self) -> &Cow<'static, str> {
+        static NAME: Cow<'static, str> = Cow::Borrowed("AbiUintWordMutator");
+        &NAME
+    }
+}
+
+impl<S> Mutator<BytesInput, S> for AbiUintWordMutator
+where
+    S: HasRand,
+{
+    fn mutate(&mut self, state: &mut S, input: &mut BytesInput) -> Result<MutationResult, Error> {
+        let bytes = input.bytes_mut();
+        let words = word_count(bytes);
+        if words == 0 {
+            return Ok(MutationResult::Skipped);
+        }
+
+        let rand = state.rand_mut();
+        let word_idx = rand.below(words.try_into().unwrap()) as usize;
+        let word = encode_word(rand, AbiType::Uint256);
+        bytes[word_range(word_idx)].copy_from_slice(&word);
+
+        Ok(MutationResult::Mutated)
+    }
+}
+
+/// Flips a random argument word between the ABI encodings of `false` and `true`.
+#[derive(Debug, Default)]
+pub struct AbiBoolWordMutator;
+
+impl AbiBoolWordMutator {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Named for AbiBoolWordMutator {
+    fn name(&self) -> &Cow<'static, str> {
+        static NAME: Cow<'static, str> = Cow::Borrowed("AbiBoolWordMutator");
+        &NAME
+    }
+}
+
+impl<S> Mutator<BytesInput, S> for AbiBoolWordMutator
+where
+    S: HasRand,
+{
+    fn mutate(&mut self, state: &mut S, input: &mut BytesInput) -> Result<MutationResult, Error> {
+        let bytes = input.bytes_mut();
+        let words = word_count(bytes);
+        if words == 0 {
+            return Ok(MutationResult::Skipped);
+        }
+
+        let rand = state.rand_mut();
+        let word_idx = rand.below(words.try_into().unwrap()) as usize;
+        let range = word_range(word_idx);
+        let was_true = bytes[range.end - 1] != 0;
+        bytes[range.clone()].fill(0);
+        bytes[range.end - 1] = u8::from(!was_true);
+
+        Ok(MutationResult::Mutated)
+    }
+}
+
+/// Replaces a random argument word with a freshly randomized `address` (zero-padded in the
+/// upper 12 bytes, as the ABI encodes it).
+#[derive(Debug, Default)]
+pub struct AbiAddressWordMutator;
+
+impl AbiAddressWordMutator {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Named for AbiAddressWordMutator {
+    fn name(&self) -> &Cow<'static, str> {
+        static NAME: Cow<'static, str> = Cow::Borrowed("AbiAddressWordMutator");
+        &NAME
+    }
+}
+
+impl<S> Mutator<BytesInput, S> for AbiAddressWordMutator
+where
+    S: HasRand,
+{
+    fn mutate(&mut self, state: &mut S, input: &mut BytesInput) -> Result<MutationResult, Error> {
+        let bytes = input.bytes_mut();
+        let words = word_count(bytes);
+        if words == 0 {
+            return Ok(MutationResult::Skipped);
+        }
+
+        let rand = state.rand_mut();
+        let word_idx = rand.below(words.try_into().unwrap()) as usize;
+        let word = encode_word(rand, AbiType::Address);
+        bytes[word_range(word_idx)].copy_from_slice(&word);
+
+        Ok(MutationResult::Mutated)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libafl_bolts::rands::StdRand;
+
+    #[test]
+    fn word_range_is_32_bytes_aligned_past_the_selector() {
+        assert_eq!(word_range(0), 4..36);
+        assert_eq!(word_range(1), 36..68);
+    }
+
+    #[test]
+    fn word_count_ignores_the_selector() {
+        assert_eq!(word_count(&[0u8; 4]), 0);
+        assert_eq!(word_count(&[0u8; 4 + 32]), 1);
+        assert_eq!(word_count(&[0u8; 4 + 32 + 16]), 1);
+    }
+
+    #[test]
+    fn encode_word_uint256_always_returns_a_known_interesting_word() {
+        let mut rand = StdRand::with_seed(0);
+        for _ in 0..32 {
+            let word = encode_word(&mut rand, AbiType::Uint256);
+            assert!(INTERESTING_UINT256_WORDS.contains(&word));
+        }
+    }
+
+    #[test]
+    fn encode_word_bool_is_always_zero_or_one_in_the_last_byte() {
+        let mut rand = StdRand::with_seed(0);
+        for _ in 0..32 {
+            let word = encode_word(&mut rand, AbiType::Bool);
+            assert_eq!(&word[..31], &[0u8; 31]);
+            assert!(word[31] == 0 || word[31] == 1);
+        }
+    }
+
+    #[test]
+    fn encode_word_address_zero_pads_the_upper_12_bytes() {
+        let mut rand = StdRand::with_seed(0);
+        let word = encode_word(&mut rand, AbiType::Address);
+        assert_eq!(&word[..12], &[0u8; 12]);
+    }
+
+    struct TestState {
+        rand: StdRand,
+    }
+
+    impl HasRand for TestState {
+        type Rand = StdRand;
+
+        fn rand(&self) -> &StdRand {
+            &self.rand
+        }
+
+        fn rand_mut(&mut self) -> &mut StdRand {
+            &mut self.rand
+        }
+    }
+
+    #[test]
+    fn bool_word_mutator_flips_true_to_false() {
+        let mut state = TestState {
+            rand: StdRand::with_seed(0),
+        };
+        let mut word = [0u8; 32];
+        word[31] = 1;
+        let mut input = BytesInput::new([&[0xeb, 0xd4, 0xb2, 0xf9][..], &word].concat());
+
+        AbiBoolWordMutator::new()
+            .mutate(&mut state, &mut input)
+            .unwrap();
+
+        assert_eq!(input.bytes_mut()[word_range(0)][31], 0);
+    }
+
+    #[test]
+    fn bool_word_mutator_flips_false_to_true() {
+        let mut state = TestState {
+            rand: StdRand::with_seed(0),
+        };
+        let mut input = BytesInput::new([&[0xeb, 0xd4, 0xb2, 0xf9][..], &[0u8; 32]].concat());
+
+        AbiBoolWordMutator::new()
+            .mutate(&mut state, &mut input)
+            .unwrap();
+
+        assert_eq!(input.bytes_mut()[word_range(0)][31], 1);
+    }
+}
@@ -0,0 +1,318 @@
+//! CmpLog: records the operand pairs seen by EVM comparison opcodes so that an
+//! input-to-state mutator can copy constants straight out of failing `require`/`revert`
+//! checks into the calldata, instead of hoping havoc stumbles onto them.
+
+use std::{borrow::Cow, marker::PhantomData};
+
+use libafl::{
+    executors::{Executor, HasObservers},
+    inputs::{BytesInput, HasMutatorBytes},
+    mutators::{MutationResult, Mutator},
+    stages::Stage,
+    state::{HasCurrentTestcase, HasRand, State, UsesState},
+    Error,
+};
+use libafl_bolts::{rands::Rand, Named};
+use revm::{
+    bytecode::opcode,
+    interpreter::{Interpreter, InterpreterTypes},
+    Inspector,
+};
+
+use crate::coverage::EdgeCoverageInspector;
+
+/// Number of call sites tracked. Keyed by program counter (masked into the map, like the
+/// edge ids of the coverage map), so a comparison that executes at the same pc every run
+/// keeps its own slot.
+pub const CMPLOG_MAP_SIZE: usize = 65536;
+
+/// A `(lhs, rhs)` operand pair captured at a comparison opcode, as big-endian 32-byte words.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CmpLogOperands {
+    pub lhs: [u8; 32],
+    pub rhs: [u8; 32],
+}
+
+static mut CMPLOG_MAP: [CmpLogOperands; CMPLOG_MAP_SIZE] = [CmpLogOperands {
+    lhs: [0; 32],
+    rhs: [0; 32],
+}; CMPLOG_MAP_SIZE];
+static mut CMPLOG_HITS: [u8; CMPLOG_MAP_SIZE] = [0; CMPLOG_MAP_SIZE];
+static mut CMPLOG_ENABLED: bool = false;
+
+/// Enable CmpLog collection for the next harness execution. Only set while a
+/// [`TracingStage`] is re-running an input; logging the operands of every comparison in
+/// every run would dominate the cost of the harness for no benefit outside tracing.
+pub fn enable_cmplog() {
+    unsafe { CMPLOG_ENABLED = true };
+}
+
+/// Disable CmpLog collection again once tracing is done.
+pub fn disable_cmplog() {
+    unsafe { CMPLOG_ENABLED = false };
+}
+
+fn is_cmplog_enabled() -> bool {
+    unsafe { CMPLOG_ENABLED }
+}
+
+/// Clear all logged operand pairs. Called at the start of every traced execution so stale
+/// pairs from a previous input don't leak into this one.
+pub fn reset_cmplog_map() {
+    unsafe {
+        CMPLOG_HITS.fill(0);
+    }
+}
+
+/// All logged `(pc, operands)` pairs from the most recently traced execution.
+pub fn cmplog_entries() -> Vec<(usize, CmpLogOperands)> {
+    unsafe {
+        CMPLOG_HITS
+            .iter()
+            .enumerate()
+            .filter(|(_, &hits)| hits > 0)
+            .map(|(pc, _)| (pc, CMPLOG_MAP[pc]))
+            .collect()
+    }
+}
+
+/// A `revm` [`Inspector`] that, when CmpLog is enabled, logs operand pairs at comparison
+/// sites so [`CmpLogI2SMutator`] has real constants to copy into the calldata. Covers
+/// `EQ`/`LT`/`GT`/`SLT`/`SGT` directly, `SUB` (Solidity routinely lowers `x == k` to
+/// `sub(x, k)` followed by a zero check rather than emitting `EQ`), and, as a catch-all for
+/// guards that don't go through any of those, the branch condition sitting on top of the
+/// stack right before every `JUMPI`.
+#[derive(Debug, Default)]
+pub struct CmpLogInspector;
+
+impl CmpLogInspector {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+fn log_operands(pc: usize, lhs: revm::primitives::U256, rhs: revm::primitives::U256) {
+    let pc = pc & (CMPLOG_MAP_SIZE - 1);
+    unsafe {
+        CMPLOG_MAP[pc] = CmpLogOperands {
+            lhs: lhs.to_be_bytes::<32>(),
+            rhs: rhs.to_be_bytes::<32>(),
+        };
+        CMPLOG_HITS[pc] = CMPLOG_HITS[pc].saturating_add(1);
+    }
+}
+
+impl<CTX, I: InterpreterTypes> Inspector<CTX, I> for CmpLogInspector {
+    fn step(&mut self, interp: &mut Interpreter<I>, _context: &mut CTX) {
+        if !is_cmplog_enabled() {
+            return;
+        }
+
+        let op = interp.bytecode.opcode();
+        let pc = interp.bytecode.pc();
+
+        if matches!(
+            op,
+            opcode::EQ | opcode::LT | opcode::GT | opcode::SLT | opcode::SGT | opcode::SUB
+        ) {
+            // All of these pop exactly two words and push one; peek them before the opcode
+            // executes and pops them off.
+            if let (Ok(lhs), Ok(rhs)) = (interp.stack.peek(0), interp.stack.peek(1)) {
+                log_operands(pc, lhs, rhs);
+            }
+        } else if op == opcode::JUMPI {
+            // JUMPI pops (destination, condition) with the destination on top, so the
+            // condition deciding this branch is the second stack item. Whatever produced it,
+            // even if it wasn't one of the opcodes above, is worth pairing against zero so the
+            // mutator can try to flip the branch outcome directly.
+            if let Ok(cond) = interp.stack.peek(1) {
+                log_operands(pc, cond, revm::primitives::U256::ZERO);
+            }
+        }
+    }
+}
+
+/// Combines edge coverage and CmpLog collection into the single [`Inspector`] the harness
+/// installs on every run; CmpLog only actually records anything while `TracingStage` has it
+/// enabled.
+#[derive(Debug, Default)]
+pub struct FuzzInspector {
+    pub coverage: EdgeCoverageInspector,
+    pub cmplog: CmpLogInspector,
+}
+
+impl FuzzInspector {
+    pub fn new() -> Self {
+        Self {
+            coverage: EdgeCoverageInspector::new(),
+            cmplog: CmpLogInspector::new(),
+        }
+    }
+}
+
+impl<CTX, I: InterpreterTypes> Inspector<CTX, I> for FuzzInspector {
+    fn step(&mut self, interp: &mut Interpreter<I>, context: &mut CTX) {
+        self.coverage.step(interp, context);
+        self.cmplog.step(interp, context);
+    }
+}
+
+fn replacement_lengths() -> [usize; 4] {
+    [32, 16, 8, 4]
+}
+
+fn try_replace(bytes: &mut Vec<u8>, needle_word: &[u8; 32], replacement_word: &[u8; 32]) -> bool {
+    for len in replacement_lengths() {
+        let needle = &needle_word[32 - len..];
+        let replacement = &replacement_word[32 - len..];
+        if needle == replacement {
+            continue;
+        }
+        if let Some(pos) = bytes
+            .windows(len)
+            .position(|window| window == needle)
+        {
+            bytes[pos..pos + len].copy_from_slice(replacement);
+            return true;
+        }
+    }
+    false
+}
+
+/// Input-to-state mutator analogous to LibAFL's `I2SRandReplace`: picks a logged comparison
+/// operand pair and overwrites an occurrence of one side in the input with the other side,
+/// so a guard like `require(x == 100)` can be satisfied in a single mutation instead of by
+/// chance.
+#[derive(Debug, Default)]
+pub struct CmpLogI2SMutator;
+
+impl CmpLogI2SMutator {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Named for CmpLogI2SMutator {
+    fn name(&self) -> &Cow<'static, str> {
+        static NAME: Cow<'static, str> = Cow::Borrowed("CmpLogI2SMutator");
+        &NAME
+    }
+}
+
+impl<S> Mutator<BytesInput, S> for CmpLogI2SMutator
+where
+    S: HasRand,
+{
+    fn mutate(&mut self, state: &mut S, input: &mut BytesInput) -> Result<MutationResult, Error> {
+        let entries = cmplog_entries();
+        if entries.is_empty() {
+            return Ok(MutationResult::Skipped);
+        }
+
+        let idx = state.rand_mut().below(entries.len().try_into().unwrap()) as usize;
+        let (_, operands) = entries[idx];
+
+        let bytes = input.bytes_mut();
+        let mut buf = bytes.to_vec();
+        let replaced = try_replace(&mut buf, &operands.lhs, &operands.rhs)
+            || try_replace(&mut buf, &operands.rhs, &operands.lhs);
+
+        if !replaced {
+            return Ok(MutationResult::Skipped);
+        }
+
+        *bytes = buf;
+        Ok(MutationResult::Mutated)
+    }
+}
+
+/// Re-executes the current testcase with CmpLog enabled so the comparison operand pairs it
+/// hits are available to [`CmpLogI2SMutator`] on the next mutation.
+#[derive(Debug)]
+pub struct TracingStage<S> {
+    phantom: PhantomData<S>,
+}
+
+impl<S> TracingStage<S> {
+    pub fn new() -> Self {
+        Self {
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<S> Default for TracingStage<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> UsesState for TracingStage<S>
+where
+    S: State,
+{
+    type State = S;
+}
+
+impl<E, EM, Z, S> Stage<E, EM, Z> for TracingStage<S>
+where
+    S: State + HasCurrentTestcase<BytesInput>,
+    E: Executor<EM, Z, State = S> + HasObservers,
+    EM: UsesState<State = S>,
+    Z: UsesState<State = S>,
+{
+    fn perform(
+        &mut self,
+        fuzzer: &mut Z,
+        executor: &mut E,
+        state: &mut S,
+        manager: &mut EM,
+    ) -> Result<(), Error> {
+        let input = state.current_testcase()?.input().clone().unwrap();
+
+        reset_cmplog_map();
+        enable_cmplog();
+        let run_result = executor.run_target(fuzzer, state, manager, &input);
+        disable_cmplog();
+        run_result?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn word(tail: u8) -> [u8; 32] {
+        let mut w = [0u8; 32];
+        w[31] = tail;
+        w
+    }
+
+    #[test]
+    fn try_replace_swaps_the_widest_matching_occurrence() {
+        let mut bytes = vec![0xeb, 0xd4, 0xb2, 0xf9];
+        bytes.extend_from_slice(&word(5));
+
+        assert!(try_replace(&mut bytes, &word(5), &word(100)));
+        assert_eq!(&bytes[4..36], &word(100));
+    }
+
+    #[test]
+    fn try_replace_falls_back_to_narrower_truncations() {
+        // The input is only 4 bytes long (the last four bytes of the needle word), so the
+        // 32/16/8-byte truncations can't match and it must fall back to the 4-byte one.
+        let mut bytes = vec![0, 0, 0, 5];
+
+        assert!(try_replace(&mut bytes, &word(5), &word(100)));
+        assert_eq!(bytes, vec![0, 0, 0, 100]);
+    }
+
+    #[test]
+    fn try_replace_returns_false_when_nothing_matches() {
+        let mut bytes = vec![1, 2, 3, 4];
+        assert!(!try_replace(&mut bytes, &word(5), &word(100)));
+        assert_eq!(bytes, vec![1, 2, 3, 4]);
+    }
+}
@@ -0,0 +1,62 @@
+//! AFL-style edge coverage collected directly from the EVM bytecode interpreter.
+//!
+//! Replaces the hand-assigned `SIGNALS` hack with a `revm` [`Inspector`] that hooks the
+//! per-opcode `step` callback and hashes consecutive program counters into a hitcounts map,
+//! the same way source/binary instrumentation would.
+
+use revm::{
+    interpreter::{Interpreter, InterpreterTypes},
+    Inspector,
+};
+
+/// Size of the edge coverage map. Must be a power of two since edge ids are masked into it.
+pub const MAP_SIZE: usize = 65536;
+
+static mut COVERAGE_MAP: [u8; MAP_SIZE] = [0; MAP_SIZE];
+static mut COVERAGE_MAP_PTR: *mut u8 = &raw mut COVERAGE_MAP as _;
+
+/// Raw pointer to the coverage map, for building a `ConstMapObserver`/`StdMapObserver` over it.
+pub fn coverage_map_ptr() -> *mut u8 {
+    unsafe { COVERAGE_MAP_PTR }
+}
+
+/// Zero the coverage map. Must be called at the start of every `harness` invocation so that
+/// each execution reports only the edges it took, not the union of all previous executions.
+pub fn reset_coverage_map() {
+    unsafe {
+        COVERAGE_MAP.fill(0);
+    }
+}
+
+/// A `revm` [`Inspector`] that derives an AFL-style edge id from consecutive program counters
+/// and bumps the corresponding raw hitcount in [`COVERAGE_MAP`] on every opcode step.
+#[derive(Debug, Default)]
+pub struct EdgeCoverageInspector {
+    prev_pc: usize,
+}
+
+impl EdgeCoverageInspector {
+    pub fn new() -> Self {
+        Self { prev_pc: 0 }
+    }
+}
+
+impl<CTX, I: InterpreterTypes> Inspector<CTX, I> for EdgeCoverageInspector {
+    fn step(&mut self, interp: &mut Interpreter<I>, _context: &mut CTX) {
+        let cur_pc = interp.bytecode.pc();
+
+        // Record an edge on every opcode, not just jumps: a straight-line path (e.g. the
+        // success path of a `require` guard, which ends in SSTORE/STOP with no JUMP/JUMPI/
+        // JUMPDEST in sight) must still produce coverage distinguishable from any other input,
+        // or the fuzzer gets no signal that it's getting closer to that path.
+        let edge_id = ((self.prev_pc >> 1) ^ cur_pc) & (MAP_SIZE - 1);
+        unsafe {
+            let slot = &mut *COVERAGE_MAP_PTR.add(edge_id);
+            // Store the raw hitcount; `HitcountsMapObserver` is responsible for bucketing it
+            // into the AFL classic ladder, and expects a real count as input to do so.
+            *slot = slot.saturating_add(1);
+        }
+
+        self.prev_pc = cur_pc;
+    }
+}
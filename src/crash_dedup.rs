@@ -0,0 +1,300 @@
+//! Classifies and de-duplicates EVM execution outcomes so the solutions directory only
+//! grows one entry per distinct `(revert/halt reason, edge-set hash)` pair, instead of one
+//! per equivalent revert.
+
+use std::{
+    borrow::Cow,
+    collections::{hash_map::DefaultHasher, HashSet},
+    hash::{Hash, Hasher},
+};
+
+use libafl::{
+    corpus::Testcase,
+    events::EventFirer,
+    executors::ExitKind,
+    feedbacks::Feedback,
+    inputs::UsesInput,
+    observers::{Observer, ObserversTuple},
+    state::State,
+    Error, HasMetadata,
+};
+use libafl_bolts::{impl_serdeany, Named};
+use serde::{Deserialize, Serialize};
+
+use crate::coverage::{coverage_map_ptr, MAP_SIZE};
+
+/// How a single EVM execution ended, as decoded from its `ExecutionResult`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum EvmOutcome {
+    #[default]
+    Success,
+    Revert(String),
+    Halt(String),
+}
+
+static mut LAST_OUTCOME: Option<EvmOutcome> = None;
+
+/// Called from the harness right after executing the transaction, so the observer below can
+/// pick the outcome up in its `post_exec`.
+pub fn record_outcome(outcome: EvmOutcome) {
+    unsafe { LAST_OUTCOME = Some(outcome) };
+}
+
+fn take_outcome() -> EvmOutcome {
+    unsafe { LAST_OUTCOME.take() }.unwrap_or_default()
+}
+
+/// Decodes the Solidity `Error(string)` ABI encoding used by `require(cond, "message")`
+/// reverts: 4-byte selector, then a 32-byte offset word (always `0x20`), a 32-byte length
+/// word, and finally the UTF-8 message bytes. Falls back to the raw hex for custom errors,
+/// panics, or unreadable output.
+pub fn decode_revert_reason(output: &[u8]) -> String {
+    const ERROR_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+    if output.len() >= 4 + 64 && output[0..4] == ERROR_SELECTOR {
+        let len = u64::from_be_bytes(output[4 + 32 + 24..4 + 32 + 32].try_into().unwrap()) as usize;
+        let start = 4 + 32 + 32;
+        if let Some(bytes) = output.get(start..start + len) {
+            if let Ok(message) = std::str::from_utf8(bytes) {
+                return message.to_string();
+            }
+        }
+    }
+    format!("0x{}", hex::encode(output))
+}
+
+/// Observer that records the [`EvmOutcome`] of the run that just finished, plus a hash of
+/// the edge coverage map at that point, so a feedback can key deduplication on the pair.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct RevertReasonObserver {
+    name: Cow<'static, str>,
+    outcome: EvmOutcome,
+    coverage_hash: u64,
+}
+
+impl RevertReasonObserver {
+    pub fn new(name: &'static str) -> Self {
+        Self {
+            name: Cow::Borrowed(name),
+            outcome: EvmOutcome::Success,
+            coverage_hash: 0,
+        }
+    }
+
+    pub fn outcome(&self) -> &EvmOutcome {
+        &self.outcome
+    }
+
+    /// The `(reason, coverage-hash)` identity of this failure; two runs that hash equal here
+    /// are considered the same bug for triage purposes.
+    pub fn dedup_key(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.outcome.hash(&mut hasher);
+        self.coverage_hash.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+impl Named for RevertReasonObserver {
+    fn name(&self) -> &Cow<'static, str> {
+        &self.name
+    }
+}
+
+impl<I, S> Observer<I, S> for RevertReasonObserver {
+    fn post_exec(
+        &mut self,
+        _state: &mut S,
+        _input: &I,
+        _exit_kind: &ExitKind,
+    ) -> Result<(), Error> {
+        self.outcome = take_outcome();
+
+        let map = unsafe { std::slice::from_raw_parts(coverage_map_ptr(), MAP_SIZE) };
+        let mut hasher = DefaultHasher::new();
+        map.hash(&mut hasher);
+        self.coverage_hash = hasher.finish();
+
+        Ok(())
+    }
+}
+
+impl_serdeany!(SeenOutcomesMetadata);
+/// Per-state record of every `(reason, coverage-hash)` pair already saved as a solution.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SeenOutcomesMetadata {
+    seen: HashSet<u64>,
+}
+
+impl_serdeany!(RevertReasonMetadata);
+/// Stashed on a testcase so the decoded revert/halt reason survives alongside it on disk.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RevertReasonMetadata {
+    pub reason: String,
+}
+
+/// Feedback that only declares an input a solution the first time its `(reason,
+/// coverage-hash)` pair is seen; every later equivalent revert/halt is discarded instead of
+/// filling up `solutions/` with duplicates.
+#[derive(Debug)]
+pub struct OutcomeDedupFeedback {
+    observer_name: Cow<'static, str>,
+}
+
+impl OutcomeDedupFeedback {
+    pub fn new(observer: &RevertReasonObserver) -> Self {
+        Self {
+            observer_name: observer.name().clone(),
+        }
+    }
+}
+
+impl Named for OutcomeDedupFeedback {
+    fn name(&self) -> &Cow<'static, str> {
+        &self.observer_name
+    }
+}
+
+impl<S> Feedback<S> for OutcomeDedupFeedback
+where
+    S: State + HasMetadata,
+{
+    fn is_interesting<EM, OT>(
+        &mut self,
+        state: &mut S,
+        _manager: &mut EM,
+        _input: &<S as UsesInput>::Input,
+        observers: &OT,
+        _exit_kind: &ExitKind,
+    ) -> Result<bool, Error>
+    where
+        EM: EventFirer<State = S>,
+        OT: ObserversTuple<S>,
+    {
+        let observer: &RevertReasonObserver = observers
+            .match_name(&self.observer_name)
+            .ok_or_else(|| Error::illegal_state("RevertReasonObserver not found"))?;
+
+        // A `Success` outcome has nothing to dedup against (there's no revert/halt reason to
+        // key on), so leave it for other feedbacks/objectives to judge.
+        if matches!(observer.outcome(), EvmOutcome::Success) {
+            return Ok(false);
+        }
+
+        let key = observer.dedup_key();
+        let is_new = state
+            .metadata_or_insert_with::<SeenOutcomesMetadata>(SeenOutcomesMetadata::default)
+            .seen
+            .insert(key);
+
+        // Suppressed whenever the TUI monitor is active; see `crate::tui_enabled`.
+        if is_new && !crate::tui_enabled() {
+            println!("New distinct outcome: {:?}", observer.outcome());
+        }
+
+        Ok(is_new)
+    }
+
+    fn append_metadata<EM, OT>(
+        &mut self,
+        _state: &mut S,
+        _manager: &mut EM,
+        observers: &OT,
+        testcase: &mut Testcase<<S as UsesInput>::Input>,
+    ) -> Result<(), Error>
+    where
+        EM: EventFirer<State = S>,
+        OT: ObserversTuple<S>,
+    {
+        let observer: &RevertReasonObserver = observers
+            .match_name(&self.observer_name)
+            .ok_or_else(|| Error::illegal_state("RevertReasonObserver not found"))?;
+
+        testcase.metadata_map_mut().insert(RevertReasonMetadata {
+            reason: format!("{:?}", observer.outcome()),
+        });
+
+        Ok(())
+    }
+}
+
+/// Feedback that's interesting whenever the run ended in a `Revert`/`Halt` rather than
+/// `Success`. Combined with [`OutcomeDedupFeedback`] via `feedback_and_fast!`, and that
+/// combination `OR`ed with [`CrashFeedback`](libafl::feedbacks::CrashFeedback) into the
+/// objective, so a run is a solution when it crashes, or when it ended in a revert/halt
+/// whose `(reason, coverage-hash)` pair has not been seen before.
+#[derive(Debug)]
+pub struct OutcomeIsFailureFeedback {
+    observer_name: Cow<'static, str>,
+}
+
+impl OutcomeIsFailureFeedback {
+    pub fn new(observer: &RevertReasonObserver) -> Self {
+        Self {
+            observer_name: observer.name().clone(),
+        }
+    }
+}
+
+impl Named for OutcomeIsFailureFeedback {
+    fn name(&self) -> &Cow<'static, str> {
+        static NAME: Cow<'static, str> = Cow::Borrowed("OutcomeIsFailureFeedback");
+        &NAME
+    }
+}
+
+impl<S> Feedback<S> for OutcomeIsFailureFeedback
+where
+    S: State,
+{
+    fn is_interesting<EM, OT>(
+        &mut self,
+        _state: &mut S,
+        _manager: &mut EM,
+        _input: &<S as UsesInput>::Input,
+        observers: &OT,
+        _exit_kind: &ExitKind,
+    ) -> Result<bool, Error>
+    where
+        EM: EventFirer<State = S>,
+        OT: ObserversTuple<S>,
+    {
+        let observer: &RevertReasonObserver = observers
+            .match_name(&self.observer_name)
+            .ok_or_else(|| Error::illegal_state("RevertReasonObserver not found"))?;
+
+        Ok(!matches!(observer.outcome(), EvmOutcome::Success))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_revert_reason_decodes_a_real_require_message() {
+        // ABI encoding of `Error(string)` for `require(x == 100, "MUST be 100")`: selector,
+        // offset word (0x20), length word (11), then the message padded to a 32-byte boundary.
+        let mut output = vec![0x08, 0xc3, 0x79, 0xa0];
+        output.extend_from_slice(&[0u8; 31]);
+        output.push(0x20);
+        output.extend_from_slice(&[0u8; 31]);
+        output.push(11);
+        output.extend_from_slice(b"MUST be 100");
+        output.extend_from_slice(&[0u8; 21]); // pad to the next 32-byte boundary
+
+        assert_eq!(decode_revert_reason(&output), "MUST be 100");
+    }
+
+    #[test]
+    fn decode_revert_reason_falls_back_to_hex_for_non_error_output() {
+        let output = vec![0xde, 0xad, 0xbe, 0xef];
+        assert_eq!(decode_revert_reason(&output), "0xdeadbeef");
+    }
+
+    #[test]
+    fn decode_revert_reason_falls_back_to_hex_when_too_short() {
+        let mut output = vec![0x08, 0xc3, 0x79, 0xa0];
+        output.extend_from_slice(&[0u8; 10]);
+        assert_eq!(decode_revert_reason(&output), format!("0x{}", hex::encode(&output)));
+    }
+}
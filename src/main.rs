@@ -1,23 +1,30 @@
+mod abi;
+mod cmplog;
+mod coverage;
+mod crash_dedup;
+
 use libafl::{
     corpus::{InMemoryCorpus, OnDiskCorpus},
-    events::SimpleEventManager,
+    events::{EventConfig, Launcher},
     executors::{inprocess::InProcessExecutor, ExitKind},
+    feedback_and_fast, feedback_or_fast,
     feedbacks::{CrashFeedback, MaxMapFeedback},
     fuzzer::{Fuzzer, StdFuzzer},
     inputs::{BytesInput, HasTargetBytes},
-    monitors::SimpleMonitor,
+    monitors::{tui::TuiMonitor, SimpleMonitor},
     mutators::{havoc_mutations::havoc_mutations, scheduled::HavocScheduledMutator},
-    observers::ConstMapObserver,
-    schedulers::QueueScheduler,
-    stages::mutational::StdMutationalStage,
-    state::StdState,
-    Evaluator,
+    observers::{HitcountsMapObserver, StdMapObserver},
+    schedulers::{powersched::PowerSchedule, IndexesLenTimeMinimizerScheduler, StdWeightedScheduler},
+    stages::{CalibrationStage, PowerMutationalStage},
+    state::{HasCorpus, StdState},
+    Error, Evaluator,
+};
+use libafl_bolts::{
+    core_affinity::Cores, rands::StdRand, shmem::StdShMemProvider, tuples::tuple_list, AsSlice,
 };
-use libafl_bolts::{nonnull_raw_mut, rands::StdRand, tuples::tuple_list, AsSlice};
 use std::{
     fs,
     path::{Path, PathBuf},
-    ptr::write,
 };
 
 use revm::{
@@ -25,23 +32,32 @@ use revm::{
     database::State,
     primitives::{Address, Bytes, TxKind, U256},
     state::Bytecode,
-    Context, ExecuteEvm, MainBuilder, MainContext,
+    Context, InspectEvm, MainBuilder, MainContext,
 };
 
-// Coverage map with explicit assignments due to the lack of instrumentation
-const SIGNALS_LEN: usize = 256;
-static mut SIGNALS: [u8; SIGNALS_LEN] = [0; SIGNALS_LEN];
-static mut SIGNALS_PTR: *mut u8 = &raw mut SIGNALS as _;
-
-fn signals_set(idx: usize) {
-    unsafe { write(SIGNALS_PTR.add(idx), 1) };
-}
+use abi::{AbiAddressWordMutator, AbiBoolWordMutator, AbiCalldataGenerator, AbiUintWordMutator};
+use cmplog::{CmpLogI2SMutator, FuzzInspector, TracingStage};
+use coverage::{coverage_map_ptr, reset_coverage_map, MAP_SIZE};
+use crash_dedup::{
+    decode_revert_reason, record_outcome, EvmOutcome, OutcomeDedupFeedback, OutcomeIsFailureFeedback,
+    RevertReasonObserver,
+};
 
 // ToFuzz.sol runtime bytecode (deployed contract code, without constructor)
 const CONTRACT_BYTECODE: &str = "60808060405260043610156011575f80fd5b5f3560e01c908163890eba6814608e575063ebd4b2f914602f575f80fd5b34608a576020366003190112608a576064600435036055575f805460ff19166001179055005b60405162461bcd60e51b815260206004820152600d60248201526c078204d5553542062652031303609c1b6044820152606490fd5b5f80fd5b34608a575f366003190112608a5760209060ff5f541615158152f3fea264697066735822122049083d31998b256e45c3c0b46511efd039b44ab5ec0d8bb7f2514ba8b0330e6b64736f6c634300081e0033";
 const CONTRACT_ADDRESS: Address = Address::new([0x13; 20]);
 const CALLER_ADDRESS: Address = Address::new([0x37; 20]);
 
+/// Whether to use the `TuiMonitor` (the default) instead of a plain `SimpleMonitor`. The TUI
+/// owns the terminal, so the raw `println!`/`eprintln!` debug output below (and in
+/// `OutcomeDedupFeedback`) is only emitted while it's disabled. Set `HELLO_LIBAFL_TUI=0` to
+/// fall back to `SimpleMonitor` and get that output back.
+pub(crate) fn tui_enabled() -> bool {
+    std::env::var("HELLO_LIBAFL_TUI")
+        .map(|v| v != "0")
+        .unwrap_or(true)
+}
+
 fn harness(input: &BytesInput) -> ExitKind {
     let target = input.target_bytes();
     let calldata = target.as_slice();
@@ -51,9 +67,10 @@ fn harness(input: &BytesInput) -> ExitKind {
         return ExitKind::Ok;
     }
 
-    // Function selector (first 4 bytes)
-    let selector = &calldata[0..4];
-    signals_set(selector[0] as usize % SIGNALS_LEN);
+    // Each execution starts from an empty coverage map: we only want the edges this run
+    // took. The cmplog map is left alone here; `TracingStage` clears it before each traced
+    // run, so its pairs stay available to the mutator across the following untraced runs.
+    reset_coverage_map();
 
     // Set contract code
     let bytecode_bytes = hex::decode(CONTRACT_BYTECODE).unwrap();
@@ -70,7 +87,7 @@ fn harness(input: &BytesInput) -> ExitKind {
         },
     );
     let ctx = Context::mainnet().with_db(&mut state_for_building);
-    let mut evm = ctx.build_mainnet();
+    let mut evm = ctx.build_mainnet_with_inspector(FuzzInspector::new());
 
     let tx: TxEnv = TxEnv::builder()
         .caller(CALLER_ADDRESS)
@@ -80,22 +97,37 @@ fn harness(input: &BytesInput) -> ExitKind {
         .build()
         .unwrap();
 
-    // Execute transaction (use transact instead of transact_commit to get state changes)
-    match evm.transact(tx) {
+    // Execute transaction under the edge coverage inspector (use inspect_tx instead of
+    // transact_commit to both get state changes and drive the per-opcode step callback).
+    match evm.inspect_tx(tx) {
         Ok(result_and_state) => {
-            // Observe execution result
-            signals_set(1);
-
             let result = &result_and_state.result;
             let state = &result_and_state.state;
 
-            // Debug: print calldata for first few executions
+            // Classify the outcome for the crash/revert dedup feedback: reaching slot-0==1
+            // is handled separately below via `panic!`, everything else is either a
+            // successful-but-uninteresting call, a `require`/`revert` with a decoded reason,
+            // or a halt (out-of-gas, stack error, ...).
+            record_outcome(match result {
+                revm::context::result::ExecutionResult::Success { .. } => EvmOutcome::Success,
+                revm::context::result::ExecutionResult::Revert { output, .. } => {
+                    EvmOutcome::Revert(decode_revert_reason(output))
+                }
+                revm::context::result::ExecutionResult::Halt { reason, .. } => {
+                    EvmOutcome::Halt(format!("{reason:?}"))
+                }
+            });
+
+            // Debug: print calldata for first few executions. Suppressed whenever the TUI
+            // monitor is active, since it owns the terminal and raw stdout/stderr writes
+            // would scribble over it.
             static mut EXEC_COUNT: u32 = 0;
-            let should_debug = unsafe {
-                EXEC_COUNT += 1;
-                EXEC_COUNT <= 5
-                    || (calldata.len() == 36 && calldata[0..4] == [0xeb, 0xd4, 0xb2, 0xf9])
-            };
+            let should_debug = !tui_enabled()
+                && unsafe {
+                    EXEC_COUNT += 1;
+                    EXEC_COUNT <= 5
+                        || (calldata.len() == 36 && calldata[0..4] == [0xeb, 0xd4, 0xb2, 0xf9])
+                };
 
             if should_debug {
                 eprintln!("\n=== DEBUG Execution ===");
@@ -120,16 +152,13 @@ fn harness(input: &BytesInput) -> ExitKind {
                         eprintln!("Storage slot 0 value: {}", flag_value);
                     }
 
-                    // Use storage value for coverage
-                    let hash = flag_value.to::<u64>() as usize;
-                    signals_set(hash % SIGNALS_LEN);
-
                     // Check if flag is true (slot-0 == 1)
                     if flag_value == U256::from(1) {
-                        signals_set(3);
-                        println!("\n🎉🎉🎉 FUZZING SUCCESS! 🎉🎉🎉");
-                        println!("🎯 Storage slot 0 value: {}", flag_value);
-                        println!("📝 Calldata (hex): {}", hex::encode(calldata));
+                        if !tui_enabled() {
+                            println!("\n🎉🎉🎉 FUZZING SUCCESS! 🎉🎉🎉");
+                            println!("🎯 Storage slot 0 value: {}", flag_value);
+                            println!("📝 Calldata (hex): {}", hex::encode(calldata));
+                        }
                         panic!(
                             "✅ Flag is set to true! Winning input: {}",
                             hex::encode(calldata)
@@ -141,110 +170,190 @@ fn harness(input: &BytesInput) -> ExitKind {
             } else if should_debug {
                 eprintln!("Account NOT in state changes");
             }
-
-            // If revert, record it
-            if !result.is_success() {
-                signals_set(2);
-            }
         }
         Err(e) => {
-            eprintln!("Transaction error: {:?}", e);
-            signals_set(4);
+            if !tui_enabled() {
+                eprintln!("Transaction error: {:?}", e);
+            }
+            record_outcome(EvmOutcome::Halt(format!("{e:?}")));
         }
     }
 
     ExitKind::Ok
 }
 
-pub fn main() {
-    delete_cache_files().expect("Failed to delete cache files");
-
+/// Runs one fuzzer worker. Called once per core by the `Launcher`, and again on every
+/// restart after the in-process `panic!("Flag is set...")` objective fires; `state` is
+/// `Some` on a restart, carrying over the corpus/solutions shared with the other workers
+/// over LLMP.
+#[allow(clippy::type_complexity)]
+fn run_client<EM>(
+    state: Option<StdState<InMemoryCorpus<BytesInput>, BytesInput, StdRand, OnDiskCorpus<BytesInput>>>,
+    mut mgr: EM,
+    _core_id: libafl_bolts::core_affinity::CoreId,
+    power_schedule: PowerSchedule,
+) -> Result<(), Error>
+where
+    EM: libafl::events::EventFirer<State = StdState<InMemoryCorpus<BytesInput>, BytesInput, StdRand, OnDiskCorpus<BytesInput>>>
+        + libafl::events::EventRestarter<State = StdState<InMemoryCorpus<BytesInput>, BytesInput, StdRand, OnDiskCorpus<BytesInput>>>
+        + libafl::events::ProgressReporter<State = StdState<InMemoryCorpus<BytesInput>, BytesInput, StdRand, OnDiskCorpus<BytesInput>>>
+        + libafl::events::EventProcessor<State = StdState<InMemoryCorpus<BytesInput>, BytesInput, StdRand, OnDiskCorpus<BytesInput>>>,
+{
     // Harness: execute contract using revm
     let mut to_fuzz = harness;
 
-    // Create observer
-    let observer = unsafe { ConstMapObserver::from_mut_ptr("signals", nonnull_raw_mut!(SIGNALS)) };
-
-    // Create feedback
+    // Create observer: AFL-style hitcounts over the edge coverage map populated by
+    // `EdgeCoverageInspector`, instead of the old hand-assigned SIGNALS map. This map lives
+    // in this process's own memory, so each `Launcher` worker gets an independent copy.
+    let observer = HitcountsMapObserver::new(unsafe {
+        StdMapObserver::from_mut_ptr("edges", coverage_map_ptr(), MAP_SIZE)
+    });
+
+    // Observer that classifies how the transaction ended (success/revert/halt) plus a hash
+    // of the edge set at that point, so the objective below can dedup equivalent failures.
+    let revert_observer = RevertReasonObserver::new("revert_reason");
+
+    // Create feedback. The objective fires on the `panic!("Flag is set...")` crash as before,
+    // but now also on a distinct revert/halt reason, deduped so the `solutions/` directory
+    // gets one entry per `(reason, coverage-hash)` pair instead of filling up with thousands
+    // of equivalent reverts.
     let mut feedback = MaxMapFeedback::new(&observer);
-    let mut objective = CrashFeedback::new();
-
-    // Create state
-    let mut state = StdState::new(
-        StdRand::new(),
-        InMemoryCorpus::new(),
-        OnDiskCorpus::new(PathBuf::from("./crashes")).unwrap(),
-        &mut feedback,
-        &mut objective,
-    )
-    .unwrap();
-
-    // Create monitor and event manager
-    let mon = SimpleMonitor::new(|s| println!("{s}"));
-    let mut mgr = SimpleEventManager::new(mon);
+    let mut objective = feedback_or_fast!(
+        CrashFeedback::new(),
+        feedback_and_fast!(
+            OutcomeIsFailureFeedback::new(&revert_observer),
+            OutcomeDedupFeedback::new(&revert_observer)
+        )
+    );
 
-    // Create scheduler and fuzzer
-    let scheduler = QueueScheduler::new();
+    // Create state, or reuse the one handed back to us across a restart
+    let mut state = match state {
+        Some(state) => state,
+        None => StdState::new(
+            StdRand::new(),
+            InMemoryCorpus::new(),
+            OnDiskCorpus::new(PathBuf::from("./crashes")).unwrap(),
+            &mut feedback,
+            &mut objective,
+        )
+        .unwrap(),
+    };
+
+    // Calibrate each new testcase's execution time and edge coverage before it's scheduled,
+    // so the power schedule below has real data to weight mutation energy with.
+    let calibration = CalibrationStage::new(&feedback);
+
+    // A power schedule favors fast, high-coverage, rarely-fuzzed testcases over the plain
+    // FIFO queue, wrapped in the indexes/len/time minimizer so shorter and faster inputs
+    // covering rare edges are prioritized further still.
+    let scheduler = IndexesLenTimeMinimizerScheduler::new(
+        &observer,
+        StdWeightedScheduler::with_schedule(&mut state, &observer, Some(power_schedule)),
+    );
     let mut fuzzer = StdFuzzer::new(scheduler, feedback, objective);
 
     // Create executor
     let mut executor = InProcessExecutor::new(
         &mut to_fuzz,
-        tuple_list!(observer),
+        tuple_list!(observer, revert_observer),
         &mut fuzzer,
         &mut state,
         &mut mgr,
     )
     .expect("Failed to create executor");
 
-    // Add initial seed inputs
-    // Try(uint256) function selector = 0xebd4b2f9
-    // Seed 1: Try(42) - wrong value, should revert
-    let seed1 = BytesInput::new(
-        hex::decode("ebd4b2f9000000000000000000000000000000000000000000000000000000000000002a")
-            .unwrap(),
-    );
-    fuzzer
-        .evaluate_input(&mut state, &mut executor, &mut mgr, &seed1)
-        .unwrap();
+    // Generate the initial corpus from the known ABI, only the first time this worker
+    // starts (a restart already carries the evolved corpus over in `state`). This produces
+    // valid `Try(uint256)`/flag-getter calldata directly instead of hoping raw printable
+    // bytes happen to decode.
+    let mut generator = AbiCalldataGenerator::new();
+    if state.must_load_initial_inputs() {
+        state
+            .generate_initial_inputs(&mut fuzzer, &mut executor, &mut generator, &mut mgr, 8)
+            .expect("Failed to generate the initial corpus");
+    }
 
-    // Seed 2: Try(1) - wrong value, should revert
-    let seed2 = BytesInput::new(
-        hex::decode("ebd4b2f90000000000000000000000000000000000000000000000000000000000000001")
-            .unwrap(),
+    // Create mutator and stages: trace the testcase with CmpLog enabled first so the
+    // input-to-state mutator has fresh comparison operands to copy into the calldata, then
+    // mutate it with an energy budget derived from the calibration above. The ABI-aware
+    // word mutators sit alongside havoc so most mutations stay valid, selector-preserving
+    // calldata rather than byte soup.
+    let mutator = HavocScheduledMutator::new(havoc_mutations().merge(tuple_list!(
+        CmpLogI2SMutator::new(),
+        AbiUintWordMutator::new(),
+        AbiBoolWordMutator::new(),
+        AbiAddressWordMutator::new(),
+    )));
+    let mut stages = tuple_list!(
+        TracingStage::new(),
+        calibration,
+        PowerMutationalStage::new(mutator)
     );
-    fuzzer
-        .evaluate_input(&mut state, &mut executor, &mut mgr, &seed2)
-        .unwrap();
 
-    // Seed 3: Try(99) - close to target, should revert
-    let seed3 = BytesInput::new(
-        hex::decode("ebd4b2f90000000000000000000000000000000000000000000000000000000000000063")
-            .unwrap(),
-    );
-    fuzzer
-        .evaluate_input(&mut state, &mut executor, &mut mgr, &seed3)
-        .unwrap();
+    // Start fuzzing
+    fuzzer.fuzz_loop(&mut stages, &mut executor, &mut state, &mut mgr)?;
+    Ok(())
+}
 
-    // // TEST ONLY: Try(100) - the correct answer! (Remove this in real fuzzing)
-    // println!("🧪 Testing with correct input Try(100) first...");
-    // let test_correct = BytesInput::new(
-    //     hex::decode("ebd4b2f90000000000000000000000000000000000000000000000000000000000000064")
-    //         .unwrap(),
-    // );
-    // fuzzer
-    //     .evaluate_input(&mut state, &mut executor, &mut mgr, &test_correct)
-    //     .unwrap();
+/// Parses the power schedule to run from `argv[1]` (`explore`, `fast`, `coe`, `lin` or
+/// `quad`), defaulting to `fast` when unset or unrecognized.
+fn power_schedule_from_args() -> PowerSchedule {
+    match std::env::args().nth(1).as_deref() {
+        Some("explore") => PowerSchedule::explore(),
+        Some("coe") => PowerSchedule::coe(),
+        Some("lin") => PowerSchedule::lin(),
+        Some("quad") => PowerSchedule::quad(),
+        _ => PowerSchedule::fast(),
+    }
+}
 
-    // Create mutator and stage
-    let mutator = HavocScheduledMutator::new(havoc_mutations());
-    let mut stages = tuple_list!(StdMutationalStage::new(mutator));
+pub fn main() {
+    delete_cache_files().expect("Failed to delete cache files");
 
-    // Start fuzzing
-    println!("Starting Solidity contract fuzzing...");
-    fuzzer
-        .fuzz_loop(&mut stages, &mut executor, &mut state, &mut mgr)
-        .expect("Error in fuzzing loop");
+    let power_schedule = power_schedule_from_args();
+
+    // One worker per selected core, corpus/solutions shared over LLMP, and
+    // auto-restart after the in-process objective panics.
+    let cores = Cores::from_cmdline("all").expect("Failed to parse cores");
+
+    let mut run_client =
+        |state, mgr, core_id| run_client(state, mgr, core_id, power_schedule);
+
+    // `TuiMonitor` by default; set `HELLO_LIBAFL_TUI=0` to fall back to `SimpleMonitor`
+    // instead (its own concrete type, hence the duplicated `Launcher::builder()` chain).
+    let launch_result = if tui_enabled() {
+        let shmem_provider = StdShMemProvider::new().expect("Failed to init shared memory");
+        let monitor = TuiMonitor::builder()
+            .title("Solidity contract fuzzer")
+            .build();
+        Launcher::builder()
+            .shmem_provider(shmem_provider)
+            .configuration(EventConfig::from_name("solidity-fuzzer"))
+            .monitor(monitor)
+            .run_client(&mut run_client)
+            .cores(&cores)
+            .broker_port(1337)
+            .build()
+            .launch()
+    } else {
+        let shmem_provider = StdShMemProvider::new().expect("Failed to init shared memory");
+        let monitor = SimpleMonitor::new(|s| println!("{s}"));
+        Launcher::builder()
+            .shmem_provider(shmem_provider)
+            .configuration(EventConfig::from_name("solidity-fuzzer"))
+            .monitor(monitor)
+            .run_client(&mut run_client)
+            .cores(&cores)
+            .broker_port(1337)
+            .build()
+            .launch()
+    };
+
+    match launch_result {
+        Ok(()) => (),
+        Err(Error::ShuttingDown) => println!("Fuzzing stopped by user. Good bye."),
+        Err(err) => panic!("Failed to run launcher: {err:?}"),
+    }
 }
 
 pub fn delete_cache_files() -> std::io::Result<()> {